@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use clap::Parser;
+use forth::{Error, Forth};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+/// Interactive REPL for the `forth` library: definitions and the stack
+/// persist across lines, so it doubles as a small scripting shell.
+#[derive(Parser)]
+#[command(author, version, about)]
+struct Cli {
+    /// File used to persist line-editing history across sessions.
+    #[arg(long, default_value = ".forth_history")]
+    history: String,
+}
+
+fn main() -> rustyline::Result<()> {
+    let cli = Cli::parse();
+    let mut forth = Forth::new();
+    let mut editor = DefaultEditor::new()?;
+    let _ = editor.load_history(&cli.history);
+
+    // rustyline only turns Ctrl-C into `ReadlineError::Interrupted` when it
+    // arrives as a keystroke on the controlling terminal. A SIGINT delivered
+    // straight to the process (e.g. `kill -INT`) bypasses that and hits the
+    // default disposition, which terminates the process. Install our own
+    // handler so that case is survived too, instead of just killing the shell.
+    let interrupted = Arc::new(AtomicBool::new(false));
+    {
+        let interrupted = interrupted.clone();
+        ctrlc::set_handler(move || interrupted.store(true, Ordering::SeqCst))
+            .expect("failed to install Ctrl-C handler");
+    }
+
+    // Lines are accumulated here until any open `:` has a matching `;`, so a
+    // colon-definition can be typed across several lines.
+    let mut pending = String::new();
+
+    loop {
+        let prompt = if pending.is_empty() { "forth> " } else { "  ...> " };
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if !pending.is_empty() {
+                    pending.push(' ');
+                }
+                pending.push_str(line.trim());
+
+                if pending.is_empty() || is_unclosed_definition(&pending) {
+                    continue;
+                }
+
+                editor.add_history_entry(pending.as_str())?;
+
+                match forth.eval(&pending) {
+                    Ok(()) => println!("{:?}", forth.stack()),
+                    Err(err) => println!("error: {}", describe(&err)),
+                }
+
+                pending.clear();
+            }
+            Err(ReadlineError::Interrupted) => {
+                // Ctrl-C cancels whatever is pending, it does not exit the shell.
+                pending.clear();
+            }
+            Err(_) if interrupted.swap(false, Ordering::SeqCst) => {
+                // A raw SIGINT interrupted the blocking read before rustyline
+                // could turn it into `Interrupted`; treat it the same way.
+                pending.clear();
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                println!("error: {err}");
+                break;
+            }
+        }
+    }
+
+    let _ = editor.save_history(&cli.history);
+
+    Ok(())
+}
+
+fn is_unclosed_definition(input: &str) -> bool {
+    let normalized = input.to_lowercase();
+    let opens = normalized.split_whitespace().filter(|&t| t == ":").count();
+    let closes = normalized.split_whitespace().filter(|&t| t == ";").count();
+
+    opens > closes
+}
+
+fn describe(err: &Error) -> String {
+    match err {
+        Error::DivisionByZero => "division by zero".to_string(),
+        Error::StackUnderflow => "stack underflow".to_string(),
+        Error::UnknownWord => "unknown word".to_string(),
+        Error::InvalidWord => "invalid word".to_string(),
+        Error::UserException(code) => format!("uncaught exception: {code}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_an_unclosed_colon_definition() {
+        assert!(is_unclosed_definition(": double dup +"));
+        assert!(!is_unclosed_definition(": double dup + ;"));
+        assert!(!is_unclosed_definition("1 2 +"));
+    }
+
+    #[test]
+    fn nested_definitions_only_close_once_balanced() {
+        assert!(is_unclosed_definition(": a : b ; "));
+        assert!(!is_unclosed_definition(": a : b ; ;"));
+    }
+
+    #[test]
+    fn describes_every_error_variant_readably() {
+        assert_eq!(describe(&Error::DivisionByZero), "division by zero");
+        assert_eq!(describe(&Error::StackUnderflow), "stack underflow");
+        assert_eq!(describe(&Error::UnknownWord), "unknown word");
+        assert_eq!(describe(&Error::InvalidWord), "invalid word");
+        assert_eq!(
+            describe(&Error::UserException(7)),
+            "uncaught exception: 7"
+        );
+    }
+}
@@ -8,12 +8,29 @@ pub type Func = Rc<dyn Fn(&mut Forth) -> Result>;
 pub struct Forth {
     stack: Vec<Value>,
     words: HashMap<String, Func>,
+    // Pristine `Rc`s for the arithmetic builtins, kept around so `compile` can
+    // tell (via `Rc::ptr_eq`) whether a word has since been redefined and
+    // must therefore no longer be folded at compile time.
+    builtins: HashMap<String, Func>,
+    // Current index of each `do`/`loop` nest, innermost last, so `i` can read
+    // the top one.
+    loop_stack: Vec<Value>,
 }
 
 #[derive(Clone)]
 enum Token {
     Val(Value),
     Fun(Func),
+    If {
+        then_ops: Vec<Token>,
+        else_ops: Vec<Token>,
+    },
+    Loop {
+        body: Vec<Token>,
+    },
+    Catch {
+        body: Vec<Token>,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -22,22 +39,38 @@ pub enum Error {
     StackUnderflow,
     UnknownWord,
     InvalidWord,
+    UserException(Value),
 }
 
 impl Forth {
     pub fn new() -> Forth {
         let mut words: HashMap<String, Func> = HashMap::new();
-        words.insert("+".to_string(), Rc::new(Self::add));
-        words.insert("-".to_string(), Rc::new(Self::sub));
-        words.insert("*".to_string(), Rc::new(Self::mul));
-        words.insert("/".to_string(), Rc::new(Self::div));
+        let add: Func = Rc::new(Self::add);
+        let sub: Func = Rc::new(Self::sub);
+        let mul: Func = Rc::new(Self::mul);
+        let div: Func = Rc::new(Self::div);
+        words.insert("+".to_string(), add.clone());
+        words.insert("-".to_string(), sub.clone());
+        words.insert("*".to_string(), mul.clone());
+        words.insert("/".to_string(), div.clone());
         words.insert("dup".to_string(), Rc::new(Self::dup));
         words.insert("drop".to_string(), Rc::new(Self::drop));
         words.insert("swap".to_string(), Rc::new(Self::swap));
         words.insert("over".to_string(), Rc::new(Self::over));
+        words.insert("i".to_string(), Rc::new(Self::loop_index));
+        words.insert("throw".to_string(), Rc::new(Self::throw));
+
+        let mut builtins: HashMap<String, Func> = HashMap::new();
+        builtins.insert("+".to_string(), add);
+        builtins.insert("-".to_string(), sub);
+        builtins.insert("*".to_string(), mul);
+        builtins.insert("/".to_string(), div);
+
         Forth {
             stack: Vec::new(),
             words,
+            builtins,
+            loop_stack: Vec::new(),
         }
     }
 
@@ -106,6 +139,76 @@ impl Forth {
         }
     }
 
+    fn loop_index(&mut self) -> Result {
+        match self.loop_stack.last() {
+            Some(&i) => Ok(self.stack.push(i)),
+            None => Err(Error::InvalidWord),
+        }
+    }
+
+    fn throw(&mut self) -> Result {
+        match self.stack.pop() {
+            // 0 is reserved for "no exception" (the code `catch` itself
+            // pushes on success), so it cannot be thrown.
+            Some(0) => Err(Error::InvalidWord),
+            Some(code) => Err(Error::UserException(code)),
+            None => Err(Error::StackUnderflow),
+        }
+    }
+
+    // Tries to fold an arithmetic builtin (`+`, `-`, `*`, `/`) about to be
+    // applied to the tail of `ops` into a single literal, or to erase it
+    // entirely via an identity peephole (`x 0 +`, `0 x +`, `x 1 *`, ...).
+    // Returns `true` if `ops` was rewritten and the builtin should not be
+    // emitted, `false` if there was nothing to fold. A literal division by
+    // zero is deliberately left unfolded rather than raised here: that would
+    // surface `Error::DivisionByZero` at compile time, before a surrounding
+    // `catch` ever gets a chance to intercept it. Leaving the builtin in
+    // place makes the error happen at runtime like any other, where `catch`
+    // can see it.
+    fn fold_arithmetic(ops: &mut Vec<Token>, word: &str) -> bool {
+        let len = ops.len();
+
+        if len >= 2 {
+            if let (Token::Val(a), Token::Val(b)) = (&ops[len - 2], &ops[len - 1]) {
+                let (a, b) = (*a, *b);
+                let result = match word {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" if b != 0 => a / b,
+                    _ => return false,
+                };
+
+                ops.truncate(len - 2);
+                ops.push(Token::Val(result));
+                return true;
+            }
+        }
+
+        if len >= 2 {
+            if let Token::Val(n) = ops[len - 1] {
+                let identity = matches!((word, n), ("+", 0) | ("-", 0) | ("*", 1) | ("/", 1));
+                if identity {
+                    ops.remove(len - 1);
+                    return true;
+                }
+            }
+
+            // Only a literal `x` is known to push one value without reading
+            // the stack, so only then can the leading `0`/`1` be dropped.
+            if let (Token::Val(n), Token::Val(_)) = (&ops[len - 2], &ops[len - 1]) {
+                let identity = matches!((word, *n), ("+", 0) | ("*", 1));
+                if identity {
+                    ops.remove(len - 2);
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
     fn compile(&mut self, input: &Vec<&str>) -> std::result::Result<Vec<Token>, Error> {
         let mut ops: Vec<Token> = Vec::new();
         let mut tokens = input.iter();
@@ -132,12 +235,92 @@ impl Forth {
 
                     return Err(Error::InvalidWord);
                 }
+                "if" => {
+                    let mut then_tokens: Vec<&str> = Vec::new();
+                    let mut else_tokens: Vec<&str> = Vec::new();
+                    let mut in_else = false;
+                    let mut depth = 0;
+                    let mut closed = false;
+
+                    while let Some(&t) = tokens.next() {
+                        match t {
+                            "if" => depth += 1,
+                            "then" if depth > 0 => depth -= 1,
+                            "then" => {
+                                closed = true;
+                                break;
+                            }
+                            "else" if depth == 0 => {
+                                in_else = true;
+                                continue;
+                            }
+                            _ => {}
+                        }
+
+                        if in_else {
+                            else_tokens.push(t);
+                        } else {
+                            then_tokens.push(t);
+                        }
+                    }
+
+                    if !closed {
+                        return Err(Error::InvalidWord);
+                    }
+
+                    let then_ops = self.compile(&then_tokens)?;
+                    let else_ops = self.compile(&else_tokens)?;
+                    ops.push(Token::If { then_ops, else_ops });
+                }
+                "do" => {
+                    let mut body_tokens: Vec<&str> = Vec::new();
+                    let mut depth = 0;
+                    let mut closed = false;
+
+                    while let Some(&t) = tokens.next() {
+                        match t {
+                            "do" => depth += 1,
+                            "loop" if depth > 0 => depth -= 1,
+                            "loop" => {
+                                closed = true;
+                                break;
+                            }
+                            _ => {}
+                        }
+
+                        body_tokens.push(t);
+                    }
+
+                    if !closed {
+                        return Err(Error::InvalidWord);
+                    }
+
+                    let body = self.compile(&body_tokens)?;
+                    ops.push(Token::Loop { body });
+                }
+                "else" | "then" | "loop" => return Err(Error::InvalidWord),
+                "catch" => match ops.pop() {
+                    Some(guarded) => ops.push(Token::Catch {
+                        body: vec![guarded],
+                    }),
+                    None => return Err(Error::InvalidWord),
+                },
                 c => {
                     if let Ok(n) = c.parse() {
                         ops.push(Token::Val(n));
                         continue;
                     }
                     if let Some(op) = self.words.get(c) {
+                        let is_builtin = matches!(c, "+" | "-" | "*" | "/")
+                            && self
+                                .builtins
+                                .get(c)
+                                .map_or(false, |builtin| Rc::ptr_eq(builtin, op));
+
+                        if is_builtin && Self::fold_arithmetic(&mut ops, c) {
+                            continue;
+                        }
+
                         ops.push(Token::Fun(op.clone()));
                         continue;
                     }
@@ -155,6 +338,44 @@ impl Forth {
             match op {
                 Token::Val(n) => self.stack.push(*n),
                 Token::Fun(f) => f(self)?,
+                Token::If { then_ops, else_ops } => match self.stack.pop() {
+                    Some(0) => self.exec(else_ops)?,
+                    Some(_) => self.exec(then_ops)?,
+                    None => return Err(Error::StackUnderflow),
+                },
+                Token::Loop { body } => {
+                    let (start, limit) = match (self.stack.pop(), self.stack.pop()) {
+                        (Some(start), Some(limit)) => (start, limit),
+                        _ => return Err(Error::StackUnderflow),
+                    };
+
+                    let mut i = start;
+                    while i < limit {
+                        self.loop_stack.push(i);
+                        let result = self.exec(body);
+                        self.loop_stack.pop();
+                        result?;
+                        i += 1;
+                    }
+                }
+                Token::Catch { body } => {
+                    let saved = self.stack.clone();
+                    match self.exec(body) {
+                        Ok(()) => self.stack.push(0),
+                        Err(err) => {
+                            // The guarded word may have already popped its
+                            // own operands before erroring, so the stack has
+                            // to be restored from a snapshot, not just
+                            // truncated back to the pre-call length.
+                            self.stack = saved;
+                            let code = match err {
+                                Error::UserException(code) => code,
+                                _ => -1,
+                            };
+                            self.stack.push(code);
+                        }
+                    }
+                }
             }
         }
 
@@ -169,3 +390,107 @@ impl Forth {
         self.exec(&tokens)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_constant_arithmetic() {
+        let mut f = Forth::new();
+        f.eval("3 4 +").unwrap();
+        assert_eq!(f.stack(), [7]);
+    }
+
+    #[test]
+    fn folds_identity_peepholes_on_either_side() {
+        let cases = [
+            ("5 0 +", 5),
+            ("0 5 +", 5),
+            ("5 0 -", 5),
+            ("5 1 *", 5),
+            ("1 5 *", 5),
+            ("5 1 /", 5),
+        ];
+
+        for (program, expected) in cases {
+            let mut f = Forth::new();
+            f.eval(program).unwrap();
+            assert_eq!(f.stack(), [expected], "{program}");
+        }
+    }
+
+    #[test]
+    fn redefined_arithmetic_word_is_not_folded() {
+        let mut f = Forth::new();
+        f.eval(": + 99 ;").unwrap();
+        f.eval("3 4 +").unwrap();
+        // If this were folded as ordinary addition it would leave `[7]`;
+        // the redefinition must win, leaving the operands untouched.
+        assert_eq!(f.stack(), [3, 4, 99]);
+    }
+
+    #[test]
+    fn literal_division_by_zero_is_a_runtime_error() {
+        let mut f = Forth::new();
+        assert_eq!(f.eval("9 0 /"), Err(Error::DivisionByZero));
+    }
+
+    #[test]
+    fn if_else_then_branches_on_top_of_stack() {
+        let mut f = Forth::new();
+        f.eval("1 if 10 else 20 then").unwrap();
+        assert_eq!(f.stack(), [10]);
+
+        let mut f = Forth::new();
+        f.eval("0 if 10 else 20 then").unwrap();
+        assert_eq!(f.stack(), [20]);
+    }
+
+    #[test]
+    fn do_loop_pushes_loop_index_via_i() {
+        let mut f = Forth::new();
+        f.eval("5 0 do i loop").unwrap();
+        assert_eq!(f.stack(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn unbalanced_control_flow_is_invalid() {
+        let mut f = Forth::new();
+        assert_eq!(f.eval("1 if 10"), Err(Error::InvalidWord));
+
+        let mut f = Forth::new();
+        assert_eq!(f.eval("5 0 do i"), Err(Error::InvalidWord));
+    }
+
+    #[test]
+    fn catch_pushes_zero_on_success() {
+        let mut f = Forth::new();
+        f.eval("3 dup + catch").unwrap();
+        assert_eq!(f.stack(), [6, 0]);
+    }
+
+    #[test]
+    fn catch_restores_stack_after_a_partial_failure() {
+        let mut f = Forth::new();
+        f.eval(": bad / ;").unwrap();
+        f.eval("8 0 bad catch").unwrap();
+        assert_eq!(f.stack(), [8, 0, -1]);
+    }
+
+    #[test]
+    fn nested_catch_frames_restore_independently() {
+        let mut f = Forth::new();
+        f.eval(": bad 1 throw ;").unwrap();
+        f.eval("1 2 bad catch catch").unwrap();
+        assert_eq!(f.stack(), [1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn throw_zero_is_rejected_rather_than_mistaken_for_success() {
+        let mut f = Forth::new();
+        f.eval(": bad 0 throw ;").unwrap();
+        f.eval("bad catch").unwrap();
+        assert_eq!(f.stack(), [-1]);
+    }
+}
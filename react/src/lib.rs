@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, cell::RefCell};
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap},
+};
 
 /// `InputCellId` is a unique identifier for an input cell.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Hash, PartialOrd, Ord)]
@@ -102,8 +106,8 @@ impl<K: NextId + Default + std::hash::Hash + Eq + Copy + Ord, T> Table<K, T> {
         self.data.values_mut()
     }
 
-    fn iter(&self) -> impl Iterator<Item = &T> {
-        self.data.values()
+    fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.data.keys().copied()
     }
 }
 
@@ -183,26 +187,79 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
         }
     }
 
-    // update all compute cells
+    // Recompute every compute cell in dependency order (Kahn's algorithm) so
+    // that a cell is only ever recomputed once all of its own compute-cell
+    // dependencies have already settled on their final value, then fire each
+    // changed cell's callbacks exactly once with that final value.
     fn react(&mut self) {
+        let ids: Vec<ComputeCellId> = self.computes.keys().collect();
 
-        for cell in self.computes.iter() {
+        let mut in_degree: HashMap<ComputeCellId, usize> = HashMap::new();
+        let mut successors: HashMap<ComputeCellId, Vec<ComputeCellId>> = HashMap::new();
+        let mut before: HashMap<ComputeCellId, T> = HashMap::new();
 
-            let dep_values: Vec<T> = cell.borrow().dependencies
+        for &id in &ids {
+            let cell = self.computes.get(id).unwrap().borrow();
+            before.insert(id, cell.value);
+
+            let degree = cell
+                .dependencies
                 .iter()
-                .filter_map(|&c| self.value(c)) 
+                .filter(|dep| matches!(dep, CellId::Compute(_)))
+                .count();
+            in_degree.insert(id, degree);
+
+            for dep in &cell.dependencies {
+                if let CellId::Compute(dep_id) = dep {
+                    successors.entry(*dep_id).or_default().push(id);
+                }
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<ComputeCellId>> = ids
+            .iter()
+            .filter(|&&id| in_degree[&id] == 0)
+            .map(|&id| Reverse(id))
+            .collect();
+
+        let mut changed: Vec<ComputeCellId> = Vec::new();
+
+        while let Some(Reverse(id)) = ready.pop() {
+            let dep_values: Vec<T> = self
+                .computes
+                .get(id)
+                .unwrap()
+                .borrow()
+                .dependencies
+                .iter()
+                .filter_map(|&dep| self.value(dep))
                 .collect();
 
-            let mut cell = cell.borrow_mut();
-            let value = (cell.func)(dep_values.as_slice());
-            if value != cell.value {
-                cell.value = value;
-                // call cell callbacks
-                for cb in cell.callbacks.iter_mut() {
-                    cb(value)
+            let mut cell = self.computes.get(id).unwrap().borrow_mut();
+            cell.value = (cell.func)(dep_values.as_slice());
+            if cell.value != before[&id] {
+                changed.push(id);
+            }
+            drop(cell);
+
+            if let Some(succs) = successors.get(&id) {
+                for &succ in succs {
+                    let degree = in_degree.get_mut(&succ).unwrap();
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push(Reverse(succ));
+                    }
                 }
             }
         }
+
+        for id in changed {
+            let mut cell = self.computes.get(id).unwrap().borrow_mut();
+            let value = cell.value;
+            for cb in cell.callbacks.iter_mut() {
+                cb(value);
+            }
+        }
     }
 
     // Sets the value of the specified input cell.
@@ -269,3 +326,58 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell as StdRefCell, rc::Rc};
+
+    #[test]
+    fn diamond_dependency_recomputes_sink_once_with_final_inputs() {
+        let mut r: Reactor<i32> = Reactor::new();
+        let input = r.create_input(1);
+        let left = r
+            .create_compute(&[CellId::Input(input)], |deps| deps[0] + 1)
+            .unwrap();
+        let right = r
+            .create_compute(&[CellId::Input(input)], |deps| deps[0] * 10)
+            .unwrap();
+        let sink = r
+            .create_compute(
+                &[CellId::Compute(left), CellId::Compute(right)],
+                |deps| deps[0] + deps[1],
+            )
+            .unwrap();
+
+        let calls = Rc::new(StdRefCell::new(Vec::new()));
+        {
+            let calls = calls.clone();
+            r.add_callback(sink, move |v| calls.borrow_mut().push(v));
+        }
+
+        r.set_value(input, 2);
+
+        assert_eq!(r.value(CellId::Compute(sink)), Some(23));
+        assert_eq!(*calls.borrow(), vec![23]);
+    }
+
+    #[test]
+    fn callback_does_not_fire_when_value_is_unchanged() {
+        let mut r: Reactor<i32> = Reactor::new();
+        let input = r.create_input(1);
+        let always_zero = r
+            .create_compute(&[CellId::Input(input)], |_| 0)
+            .unwrap();
+
+        let calls = Rc::new(StdRefCell::new(0));
+        {
+            let calls = calls.clone();
+            r.add_callback(always_zero, move |_| *calls.borrow_mut() += 1);
+        }
+
+        r.set_value(input, 2);
+        r.set_value(input, 3);
+
+        assert_eq!(*calls.borrow(), 0);
+    }
+}